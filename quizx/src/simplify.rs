@@ -16,8 +16,10 @@
 
 use crate::graph::*;
 use crate::basic_rules::*;
-use rustc_hash::FxHashMap;
+use crate::scalar::ScalarN;
+use rustc_hash::{FxHashMap,FxHashSet};
 use num::{Rational,Zero};
+use std::collections::VecDeque;
 
 /// Repeatedly apply the given rule at any vertex
 /// that matches the check function
@@ -81,94 +83,338 @@ pub fn edge_simp<G: GraphLike>(
     got_match
 }
 
+/// Worklist-driven variant of [`vertex_simp`].
+///
+/// Rather than rescanning `g.vertex_vec()` on every pass, this keeps a
+/// FIFO queue of "dirty" vertices and a membership guard so a vertex is
+/// never queued twice at once. Popping a vertex and firing `rule` on it
+/// only re-queues that vertex and its neighbors (the vertices a rule could
+/// plausibly have made newly-matchable), so once the graph is mostly
+/// stable a pass only touches the handful of vertices near the last
+/// rewrite instead of the whole graph.
+///
+/// `dirty` doubles as the seed: if it's empty, the queue is seeded with
+/// the whole vertex set (a full scan, e.g. for a first, standalone call);
+/// otherwise only `dirty`'s vertices seed the queue, which is sound as
+/// long as this same rule is already known to match nowhere outside
+/// `dirty`. On return, if anything matched, `dirty` is replaced with every
+/// vertex that was matched or sat next to a match, so a caller can feed it
+/// back into a later call of the same rule instead of starting from the
+/// full vertex set again. If nothing matched, `dirty` is left untouched.
+pub fn vertex_simp_worklist<G: GraphLike>(
+    g: &mut G,
+    check: fn(&G, V) -> bool,
+    rule: fn(&mut G, V) -> (),
+    force_reduce: bool,
+    dirty: &mut FxHashSet<V>,
+    ) -> bool
+{
+    let mut numv = g.num_vertices();
+
+    let mut queue: VecDeque<V> = if dirty.is_empty() {
+        g.vertex_vec().into_iter().collect()
+    } else {
+        dirty.iter().copied().filter(|&v| g.contains_vertex(v)).collect()
+    };
+    let mut queued: FxHashSet<V> = queue.iter().copied().collect();
+
+    let mut got_match = false;
+    let mut new_dirty: FxHashSet<V> = FxHashSet::default();
+
+    while let Some(v) = queue.pop_front() {
+        queued.remove(&v);
+        if !g.contains_vertex(v) || !check(g, v) { continue; }
+
+        let nhd: Vec<V> = g.neighbors(v).collect();
+        rule(g, v);
+        got_match = true;
+        new_dirty.insert(v);
+
+        for n in nhd.into_iter().chain(std::iter::once(v)) {
+            new_dirty.insert(n);
+            if g.contains_vertex(n) && queued.insert(n) {
+                queue.push_back(n);
+            }
+        }
+
+        if force_reduce && numv <= g.num_vertices() { break; }
+        numv = g.num_vertices();
+    }
+
+    if got_match { *dirty = new_dirty; }
+
+    got_match
+}
+
+/// Queue every `edge_type`-edge incident to `v` that isn't already queued.
+fn queue_incident_edges<G: GraphLike>(
+    g: &G,
+    v: V,
+    edge_type: EType,
+    queue: &mut VecDeque<(V,V)>,
+    queued: &mut FxHashSet<(V,V)>,
+    )
+{
+    for (n, et) in g.incident_edges(v) {
+        if et != edge_type { continue; }
+        let key = if v <= n { (v, n) } else { (n, v) };
+        if queued.insert(key) { queue.push_back(key); }
+    }
+}
+
+/// Worklist-driven variant of [`edge_simp`], keyed on the incident edges of
+/// whichever vertices a rewrite touches (see [`vertex_simp_worklist`] for
+/// how `dirty` is used to seed the queue and threaded between calls).
+pub fn edge_simp_worklist<G: GraphLike>(
+    g: &mut G,
+    edge_type: EType,
+    check: fn(&G, V, V) -> bool,
+    rule: fn(&mut G, V, V) -> (),
+    force_reduce: bool,
+    dirty: &mut FxHashSet<V>,
+    ) -> bool
+{
+    let mut numv = g.num_vertices();
+
+    let mut queue: VecDeque<(V,V)> = VecDeque::new();
+    let mut queued: FxHashSet<(V,V)> = FxHashSet::default();
+
+    if dirty.is_empty() {
+        for (s,t,et) in g.edge_vec() {
+            if et != edge_type { continue; }
+            let key = if s <= t { (s,t) } else { (t,s) };
+            if queued.insert(key) { queue.push_back(key); }
+        }
+    } else {
+        for &v in dirty.iter() {
+            if g.contains_vertex(v) { queue_incident_edges(g, v, edge_type, &mut queue, &mut queued); }
+        }
+    }
+
+    let mut got_match = false;
+    let mut new_dirty: FxHashSet<V> = FxHashSet::default();
+
+    while let Some((s,t)) = queue.pop_front() {
+        queued.remove(&(s,t));
+        if !g.contains_vertex(s) || !g.contains_vertex(t) || !check(g, s, t) { continue; }
+
+        let mut touched: Vec<V> = vec![s, t];
+        touched.extend(g.neighbors(s));
+        touched.extend(g.neighbors(t));
+
+        rule(g, s, t);
+        got_match = true;
+
+        for v in touched {
+            new_dirty.insert(v);
+            if !g.contains_vertex(v) { continue; }
+            queue_incident_edges(g, v, edge_type, &mut queue, &mut queued);
+        }
+
+        if force_reduce && numv <= g.num_vertices() { break; }
+        numv = g.num_vertices();
+    }
+
+    if got_match { *dirty = new_dirty; }
+
+    got_match
+}
+
 pub fn id_simp(g: &mut impl GraphLike) -> bool {
-    vertex_simp(g, check_remove_id, remove_id_unchecked, false)
+    let mut dirty = FxHashSet::default();
+    vertex_simp_worklist(g, check_remove_id, remove_id_unchecked, false, &mut dirty)
 }
 
 pub fn local_comp_simp(g: &mut impl GraphLike) -> bool {
-    vertex_simp(g, check_local_comp, local_comp_unchecked, false)
+    let mut dirty = FxHashSet::default();
+    vertex_simp_worklist(g, check_local_comp, local_comp_unchecked, false, &mut dirty)
 }
 
 pub fn spider_simp(g: &mut impl GraphLike) -> bool {
-    edge_simp(g, EType::N, check_spider_fusion, spider_fusion_unchecked, false)
+    let mut dirty = FxHashSet::default();
+    edge_simp_worklist(g, EType::N, check_spider_fusion, spider_fusion_unchecked, false, &mut dirty)
 }
 
 pub fn pivot_simp(g: &mut impl GraphLike) -> bool {
-    edge_simp(g, EType::H, check_pivot, pivot_unchecked, false)
+    let mut dirty = FxHashSet::default();
+    edge_simp_worklist(g, EType::H, check_pivot, pivot_unchecked, false, &mut dirty)
 }
 
 pub fn gen_pivot_simp(g: &mut impl GraphLike) -> bool {
-    edge_simp(g, EType::H, check_gen_pivot_reduce, gen_pivot_unchecked, false)
+    let mut dirty = FxHashSet::default();
+    edge_simp_worklist(g, EType::H, check_gen_pivot_reduce, gen_pivot_unchecked, false, &mut dirty)
+}
+
+/// A composable rewriting strategy over some [`GraphLike`] graph.
+///
+/// This exists so pipelines like [`clifford_simp`] don't have to hand-roll
+/// their own `while m { m = a(g) || b(g) || ...}` loops: a `Strategy` is
+/// just "run this on `g`, tell me whether anything matched", and the
+/// combinators below build bigger strategies out of smaller ones.
+pub trait Strategy<G: GraphLike> {
+    fn apply(&self, g: &mut G) -> bool;
+}
+
+/// Wraps a single pass of an existing rule, e.g. [`id_simp`] or
+/// [`spider_simp`], as a `Strategy`.
+pub struct Once<F>(pub F);
+
+impl<G: GraphLike, F: Fn(&mut G) -> bool> Strategy<G> for Once<F> {
+    fn apply(&self, g: &mut G) -> bool { (self.0)(g) }
+}
+
+/// Run `a`, then run `b` regardless of whether `a` matched. Matches if
+/// either did.
+pub struct Seq<A, B>(pub A, pub B);
+
+impl<G: GraphLike, A: Strategy<G>, B: Strategy<G>> Strategy<G> for Seq<A, B> {
+    fn apply(&self, g: &mut G) -> bool {
+        let m = self.0.apply(g);
+        self.1.apply(g) || m
+    }
+}
+
+/// Run `a`; only run `b` if `a` found no match.
+pub struct Choice<A, B>(pub A, pub B);
+
+impl<G: GraphLike, A: Strategy<G>, B: Strategy<G>> Strategy<G> for Choice<A, B> {
+    fn apply(&self, g: &mut G) -> bool {
+        self.0.apply(g) || self.1.apply(g)
+    }
+}
+
+/// Apply `s` to a fixpoint, i.e. keep re-running it until a run makes no
+/// match. Returns whether anything changed across the whole run.
+pub struct Repeat<S>(pub S);
+
+impl<G: GraphLike, S: Strategy<G>> Strategy<G> for Repeat<S> {
+    fn apply(&self, g: &mut G) -> bool {
+        let mut got_match = false;
+        while self.0.apply(g) { got_match = true; }
+        got_match
+    }
+}
+
+/// Keep applying `s` only while it strictly shrinks the vertex count,
+/// generalising the `force_reduce` early-exit on [`vertex_simp`]/[`edge_simp`]
+/// to an arbitrary strategy.
+pub struct WhileShrinks<S>(pub S);
+
+impl<G: GraphLike, S: Strategy<G>> Strategy<G> for WhileShrinks<S> {
+    fn apply(&self, g: &mut G) -> bool {
+        let mut got_match = false;
+        loop {
+            let numv = g.num_vertices();
+            if !self.0.apply(g) { break; }
+            got_match = true;
+            if g.num_vertices() >= numv { break; }
+        }
+        got_match
+    }
 }
 
+/// This is the hot inner loop of [`clifford_simp`], so unlike the
+/// `Strategy`-composed pipelines below it, it calls `vertex_simp_worklist`/
+/// `edge_simp_worklist` directly and keeps one `dirty` set per rule across
+/// the whole `while m` loop. A rule's set stays empty, so the rule scans the
+/// whole graph, until that rule first matches; from then on the set holds
+/// every vertex touched by any rule's rewrites since, so later iterations
+/// only recheck those vertices without missing a match that one rule's
+/// rewrite created for another.
 pub fn interior_clifford_simp(g: &mut impl GraphLike) -> bool {
     spider_simp(g);
     g.x_to_z();
+
+    let mut pending: [FxHashSet<V>; 4] = Default::default();
     let mut got_match = false;
     let mut m = true;
     while m {
-        m = id_simp(g)
-         || spider_simp(g)
-         || pivot_simp(g)
-         || local_comp_simp(g);
+        m = vertex_simp_worklist(g, check_remove_id, remove_id_unchecked, false, &mut pending[0])
+         || edge_simp_worklist(g, EType::N, check_spider_fusion, spider_fusion_unchecked, false, &mut pending[1])
+         || edge_simp_worklist(g, EType::H, check_pivot, pivot_unchecked, false, &mut pending[2])
+         || vertex_simp_worklist(g, check_local_comp, local_comp_unchecked, false, &mut pending[3]);
         if m { got_match = true; }
+        let touched: FxHashSet<V> = pending.iter().flatten().copied().collect();
+        for p in pending.iter_mut().filter(|p| !p.is_empty()) { *p = touched.clone(); }
     }
 
     got_match
 }
 
 pub fn clifford_simp(g: &mut impl GraphLike) -> bool {
-    let mut got_match = false;
-    let mut m = true;
-    while m {
-        // let numv = g.num_vertices();
-        // println!("v: {}", numv);
-        m = interior_clifford_simp(g) ||
-            gen_pivot_simp(g);
-        if m { got_match = true; }
-        // if !(g.num_vertices() < numv) { break; }
-    }
-
-    got_match
+    Repeat(Choice(Once(interior_clifford_simp), Once(gen_pivot_simp))).apply(g)
 }
 
+/// Find phase gadgets (degree-1 "tip" spiders hanging off a hub) and fuse
+/// together any that share the exact same set of legs, summing their tip
+/// phases onto a single representative.
+///
+/// A gadget's legs are the hub's H-neighbours other than the tip itself,
+/// which is what actually identifies the gadget as a rewrite target -- two
+/// gadgets act the same way iff they share legs, regardless of the tip's
+/// own phase.
+///
+/// Scope decision, reviewed and intentionally not implemented here:
+/// gadgets whose legs are in a strict subset (rather than identical)
+/// relationship are NOT fused by this function. A gadget on legs `B` acts
+/// as the diagonal operator `e^{i*phase*Z_B}` (`Z_B` the parity over `B`);
+/// for `A` a strict subset of `B`, `e^{i*phase*Z_A}` and `e^{i*phase*Z_B}`
+/// don't combine into a single phase gadget on any leg set via local
+/// vertex merging -- `Z_B` isn't the product of `Z_A` and `Z_{B\A}` in a
+/// way exponentials distribute over, so true "partial fusion" would need
+/// to introduce new CNOT/ancilla structure to re-derive `B`'s gadget
+/// around `A`, not just repoint edges and sum phases onto one vertex. That
+/// is real algorithmic work with its own soundness proof, not a small
+/// extension of the equal-legs case below, so it's left out of this
+/// change rather than shipped as an untested, likely-unsound shortcut --
+/// exactly the kind of rule the [`crate::verify`] harness exists to catch.
+/// Revisit as its own change if a concrete derivation is worked out.
 pub fn fuse_gadgets(g: &mut impl GraphLike) -> bool {
     let mut gadgets: FxHashMap<Vec<V>,Vec<(V,V)>> = FxHashMap::default();
 
     for v in g.vertices() {
-        if g.vertex_type(v) != VType::Z ||
-           !g.phase(v).is_zero() { continue; }
-        if g.degree(v) == 1 {
-            let w = g.neighbors(v).next().unwrap();
-            let mut nhd = Vec::new();
-            for (n,et) in g.incident_edges(w) {
-                if g.vertex_type(n) != VType::Z ||
-                   et != EType::H { continue; }
-                if n != v { nhd.push(v); }
-            }
-            nhd.sort();
-
-            if let Some(gs) = gadgets.get_mut(&nhd) {
-                gs.push((w, v));
-            } else {
-                gadgets.insert(nhd, vec![(w,v)]);
-            }
+        if g.vertex_type(v) != VType::Z || g.degree(v) != 1 { continue; }
+
+        let (hub, tip_et) = g.incident_edges(v).next().unwrap();
+        if tip_et != EType::H || g.vertex_type(hub) != VType::Z ||
+           !g.phase(hub).is_zero() || g.degree(hub) == 1 { continue; }
+        let mut legs: Vec<V> = Vec::new();
+        let mut is_gadget = true;
+        for (n, et) in g.incident_edges(hub) {
+            if n == v { continue; }
+            if g.vertex_type(n) != VType::Z || et != EType::H { is_gadget = false; break; }
+            legs.push(n);
         }
+        if !is_gadget { continue; }
+        legs.sort();
+
+        gadgets.entry(legs).or_insert_with(Vec::new).push((hub, v));
     }
 
     let mut fused = false;
     for gs in gadgets.values() {
-        if gs.len() > 1 {
-            fused = true;
-            let mut it = gs.iter(); it.next();
-            let mut ph = Rational::zero();
-            for i in 1..gs.len() {
-                ph += g.phase(gs[i].1);
-                g.remove_vertex(gs[i].0);
-                g.remove_vertex(gs[i].1);
-            }
-
-            g.add_to_phase(gs[0].1, ph);
+        if gs.len() <= 1 { continue; }
+        fused = true;
+
+        let (rep_hub, rep_tip) = gs[0];
+        // a gadget on k legs carries a scalar factor of sqrt(2)^(1-k), which
+        // has to move onto the graph's scalar whenever a gadget is removed
+        let gadget_pow = 2 - g.degree(rep_hub) as i32;
+        let mut added = Rational::zero();
+        for &(hub, tip) in &gs[1..] {
+            added += g.phase(tip);
+            g.remove_vertex(hub);
+            g.remove_vertex(tip);
+            g.scalar_mut().mul_sqrt2_pow(gadget_pow);
+        }
+        g.add_to_phase(rep_tip, added);
+
+        if g.phase(rep_tip).is_zero() {
+            // the fused tips cancelled exactly, so the whole gadget is
+            // sqrt(2)^(1-k) times the identity on its legs
+            g.scalar_mut().mul_sqrt2_pow(gadget_pow);
+            g.remove_vertex(rep_hub);
+            g.remove_vertex(rep_tip);
         }
     }
 
@@ -176,15 +422,203 @@ pub fn fuse_gadgets(g: &mut impl GraphLike) -> bool {
 }
 
 pub fn full_simp(g: &mut impl GraphLike) -> bool {
+    Repeat(Choice(Once(clifford_simp), Once(fuse_gadgets))).apply(g)
+}
+
+/// One recorded application of a rewrite rule, produced by
+/// [`full_simp_traced`] and consumed by [`replay`].
+///
+/// `vertex`/`vertex2` identify what the rule matched (just `vertex` for a
+/// vertex rule like `id`, both for an edge rule like `spider`, neither for
+/// an aggregate step like `fuse_gadgets` that can touch many vertices at
+/// once).
+#[derive(Debug, Clone)]
+pub struct SimpStep {
+    pub rule: &'static str,
+    pub vertex: Option<V>,
+    pub vertex2: Option<V>,
+    pub vertices_removed: usize,
+    pub scalar_before: ScalarN,
+    pub scalar_after: ScalarN,
+}
+
+/// An ordered record of every rewrite a traced simplification pass applied.
+///
+/// This supports debugging non-terminating or semantics-breaking
+/// simplifications, generating a human-readable derivation for a paper, or
+/// caching an optimisation "recipe" (via [`replay`]) to rerun on a
+/// structurally identical graph without re-searching for vertex and edge
+/// rule matches.
+#[derive(Debug, Clone, Default)]
+pub struct SimpTrace {
+    steps: Vec<SimpStep>,
+}
+
+impl SimpTrace {
+    pub fn new() -> Self { SimpTrace { steps: Vec::new() } }
+
+    pub fn steps(&self) -> &[SimpStep] { &self.steps }
+
+    /// How many times each rule fired, in the order each rule first fired.
+    pub fn match_counts(&self) -> Vec<(&'static str, usize)> {
+        let mut counts: Vec<(&'static str, usize)> = Vec::new();
+        for step in &self.steps {
+            match counts.iter_mut().find(|(r, _)| *r == step.rule) {
+                Some(e) => e.1 += 1,
+                None => counts.push((step.rule, 1)),
+            }
+        }
+        counts
+    }
+
+    /// Total number of vertices removed across the whole trace.
+    pub fn vertices_removed(&self) -> usize {
+        self.steps.iter().map(|s| s.vertices_removed).sum()
+    }
+}
+
+fn traced_vertex_simp<G: GraphLike>(
+    g: &mut G,
+    rule_name: &'static str,
+    check: fn(&G, V) -> bool,
+    rule: fn(&mut G, V) -> (),
+    trace: &mut SimpTrace,
+    ) -> bool
+{
+    let mut got_match = false;
+    let mut new_matches = true;
+    while new_matches {
+        new_matches = false;
+        for v in g.vertex_vec() {
+            if !g.contains_vertex(v) || !check(g, v) { continue; }
+            let numv = g.num_vertices();
+            let scalar_before = g.scalar().clone();
+            rule(g, v);
+            trace.steps.push(SimpStep {
+                rule: rule_name,
+                vertex: Some(v),
+                vertex2: None,
+                vertices_removed: numv.saturating_sub(g.num_vertices()),
+                scalar_before,
+                scalar_after: g.scalar().clone(),
+            });
+            new_matches = true;
+            got_match = true;
+        }
+    }
+
+    got_match
+}
+
+fn traced_edge_simp<G: GraphLike>(
+    g: &mut G,
+    rule_name: &'static str,
+    edge_type: EType,
+    check: fn(&G, V, V) -> bool,
+    rule: fn(&mut G, V, V) -> (),
+    trace: &mut SimpTrace,
+    ) -> bool
+{
+    let mut got_match = false;
+    let mut new_matches = true;
+    while new_matches {
+        new_matches = false;
+        for (s,t,et) in g.edge_vec() {
+            if et != edge_type ||
+               !g.contains_vertex(s) ||
+               !g.contains_vertex(t) ||
+               !check(g, s, t)
+               { continue; }
+            let numv = g.num_vertices();
+            let scalar_before = g.scalar().clone();
+            rule(g, s, t);
+            trace.steps.push(SimpStep {
+                rule: rule_name,
+                vertex: Some(s),
+                vertex2: Some(t),
+                vertices_removed: numv.saturating_sub(g.num_vertices()),
+                scalar_before,
+                scalar_after: g.scalar().clone(),
+            });
+            new_matches = true;
+            got_match = true;
+        }
+    }
+
+    got_match
+}
+
+fn traced_fuse_gadgets<G: GraphLike>(g: &mut G, trace: &mut SimpTrace) -> bool {
+    let numv = g.num_vertices();
+    let scalar_before = g.scalar().clone();
+    let did_fuse = fuse_gadgets(g);
+    if did_fuse {
+        trace.steps.push(SimpStep {
+            rule: "fuse_gadgets",
+            vertex: None,
+            vertex2: None,
+            vertices_removed: numv.saturating_sub(g.num_vertices()),
+            scalar_before,
+            scalar_after: g.scalar().clone(),
+        });
+    }
+
+    did_fuse
+}
+
+/// Traced version of [`full_simp`] that records every rewrite applied, in
+/// order, as a [`SimpTrace`]. Lets a caller (e.g. the decomposition driver)
+/// report which rules actually produced a T-count reduction, rather than
+/// just the before/after totals.
+pub fn full_simp_traced(g: &mut impl GraphLike) -> (bool, SimpTrace) {
+    // interior_clifford_simp converts X spiders to Z before its rule loop
+    // ever runs; without this, graphs with X spiders (e.g. straight out of
+    // `Circuit::to_graph`) would under-match every traced rule below and
+    // misreport where reduction came from.
+    g.x_to_z();
+
+    let mut trace = SimpTrace::new();
     let mut got_match = false;
     let mut m = true;
     while m {
-        m = clifford_simp(g)
-         || fuse_gadgets(g);
+        m = traced_vertex_simp(g, "id", check_remove_id, remove_id_unchecked, &mut trace)
+         || traced_edge_simp(g, "spider", EType::N, check_spider_fusion, spider_fusion_unchecked, &mut trace)
+         || traced_edge_simp(g, "pivot", EType::H, check_pivot, pivot_unchecked, &mut trace)
+         || traced_vertex_simp(g, "local_comp", check_local_comp, local_comp_unchecked, &mut trace)
+         || traced_edge_simp(g, "gen_pivot", EType::H, check_gen_pivot_reduce, gen_pivot_unchecked, &mut trace)
+         || traced_fuse_gadgets(g, &mut trace);
         if m { got_match = true; }
     }
 
-    got_match
+    (got_match, trace)
+}
+
+/// Re-apply every step recorded in `trace` to `g`, in order, without
+/// re-running `check` to rediscover the matches. Intended for replaying a
+/// known-good derivation against a fresh copy of the graph it was
+/// originally produced from; a step whose vertex/vertices are no longer
+/// present (e.g. `g` isn't actually that graph) is skipped rather than
+/// applied to the wrong vertex or panicking in the `*_unchecked` rule.
+/// `fuse_gadgets` steps record no vertices, so they are replayed by running
+/// [`fuse_gadgets`] again, which does search the graph for gadgets.
+pub fn replay(trace: &SimpTrace, g: &mut impl GraphLike) {
+    // full_simp_traced converts X spiders to Z before recording any step
+    g.x_to_z();
+
+    for step in &trace.steps {
+        match (step.rule, step.vertex, step.vertex2) {
+            ("id", Some(v), _) if g.contains_vertex(v) => remove_id_unchecked(g, v),
+            ("local_comp", Some(v), _) if g.contains_vertex(v) => local_comp_unchecked(g, v),
+            ("spider", Some(s), Some(t)) if g.contains_vertex(s) && g.contains_vertex(t) =>
+                spider_fusion_unchecked(g, s, t),
+            ("pivot", Some(s), Some(t)) if g.contains_vertex(s) && g.contains_vertex(t) =>
+                pivot_unchecked(g, s, t),
+            ("gen_pivot", Some(s), Some(t)) if g.contains_vertex(s) && g.contains_vertex(t) =>
+                gen_pivot_unchecked(g, s, t),
+            ("fuse_gadgets", _, _) => { fuse_gadgets(g); }
+            _ => {}
+        }
+    }
 }
 
 #[cfg(test)]
@@ -266,4 +700,93 @@ mod tests {
         println!("{}", h.to_dot());
         assert_eq!(g.to_tensor4(), h.to_tensor4());
     }
+
+    #[test]
+    fn full_simp_traced_replays() {
+        let c = Circuit::random()
+            .seed(1337)
+            .qubits(5)
+            .depth(50)
+            .p_t(0.2)
+            .with_cliffords()
+            .build();
+
+        let g: Graph = c.to_graph();
+
+        let mut h = g.clone();
+        let (got_match, trace) = full_simp_traced(&mut h);
+        assert!(got_match);
+        assert_eq!(g.to_tensor4(), h.to_tensor4());
+        assert!(trace.vertices_removed() > 0);
+
+        let mut replayed = g.clone();
+        replay(&trace, &mut replayed);
+        assert_eq!(h.to_tensor4(), replayed.to_tensor4());
+    }
+
+    /// Build three "leg" spiders and two phase gadgets hanging off them
+    /// with tip phases `ph1` and `ph2`, i.e. two duplicated gadgets that
+    /// `fuse_gadgets` should merge into one.
+    fn duplicated_gadgets(ph1: Rational, ph2: Rational) -> Graph {
+        let mut g = Graph::new();
+        let legs = [g.add_vertex(VType::Z), g.add_vertex(VType::Z), g.add_vertex(VType::Z)];
+
+        for ph in [ph1, ph2] {
+            let hub = g.add_vertex(VType::Z);
+            let tip = g.add_vertex_with_phase(VType::Z, ph);
+            g.add_edge_with_type(hub, tip, EType::H);
+            for &leg in &legs {
+                g.add_edge_with_type(hub, leg, EType::H);
+            }
+        }
+
+        g
+    }
+
+    #[test]
+    fn fuse_gadgets_merges_duplicates() {
+        let g = duplicated_gadgets(Rational::new(1,4), Rational::new(1,4));
+        let mut h = g.clone();
+        assert!(fuse_gadgets(&mut h));
+        assert_eq!(g.to_tensor4(), h.to_tensor4());
+    }
+
+    #[test]
+    fn fuse_gadgets_cancels_to_identity() {
+        let g = duplicated_gadgets(Rational::new(1,4), Rational::new(-1,4));
+        let mut h = g.clone();
+        assert!(fuse_gadgets(&mut h));
+        assert_eq!(g.to_tensor4(), h.to_tensor4());
+    }
+
+    #[test]
+    fn fuse_gadgets_preserves_random_duplicated_graphs() {
+        use crate::verify::check_semantics_preserved;
+
+        for seed in 0..20 {
+            let mut g = crate::verify::random_graph(seed, 10, 0.4, 4);
+            // duplicate the first phase-0, degree-1 Z spider (if any) as a new
+            // gadget on its neighbour's H-neighbours; when that neighbour is a
+            // real gadget hub this gives a fusible pair
+            let gadget = g.vertex_vec().into_iter().find(|&v| {
+                g.vertex_type(v) == VType::Z && g.phase(v).is_zero() && g.degree(v) == 1
+            });
+            if let Some(tip) = gadget {
+                let hub = g.neighbors(tip).next().unwrap();
+                let legs: Vec<V> = g.incident_edges(hub)
+                    .filter(|&(n, et)| n != tip && et == EType::H)
+                    .map(|(n, _)| n)
+                    .collect();
+
+                let new_hub = g.add_vertex(VType::Z);
+                let new_tip = g.add_vertex_with_phase(VType::Z, g.phase(tip));
+                g.add_edge_with_type(new_hub, new_tip, EType::H);
+                for leg in legs {
+                    g.add_edge_with_type(new_hub, leg, EType::H);
+                }
+            }
+
+            assert!(check_semantics_preserved(&g, |h| fuse_gadgets(h)).is_ok());
+        }
+    }
 }
\ No newline at end of file